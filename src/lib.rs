@@ -1,7 +1,16 @@
-use rand::seq::SliceRandom;
+use rand::{
+    distributions::{
+        Distribution,
+        WeightedIndex,
+    },
+    Rng,
+};
 use std::{
     cmp::max,
-    collections::HashMap,
+    collections::{
+        BinaryHeap,
+        HashMap,
+    },
 };
 #[cfg(feature = "saveload")]
 use std::fs::File;
@@ -16,10 +25,18 @@ pub const EOS: &str = "__eos__";
 pub const DEFAULT_CONTEXT: usize = 2;
 pub const DEFAULT_SMOOTHING: bool = true;
 pub const DEFAULT_SAMPLING: f32 = 0.8;
+pub const DEFAULT_BEAM_WIDTH: usize = 5;
+pub const DEFAULT_TEMPERATURE: f32 = 1.0;
+pub const DEFAULT_FLOOR_PROBABILITY: f32 = 1e-10;
+pub const DEFAULT_WEIGHTED: bool = false;
 
 #[cfg(feature = "saveload")]
 const SPLIT_TOKEN: &str = "<[SP]>";
 
+/// Convenience alias for fallible save/load operations.
+#[cfg(feature = "saveload")]
+type Result_<T> = Result<T, Box<dyn std::error::Error>>;
+
 /// Splits text by whitespaces.
 pub fn tokenize(text: String) -> Vec<String>
 {
@@ -151,6 +168,97 @@ pub fn tiny_corpus() -> Vec<String>
         .collect::<Vec<_>>()
 }
 
+/// Options for [`Model::generate_with_options()`].
+///
+/// # Usage
+///
+/// ```
+/// use n_gram::GenerateOptions;
+///
+/// let options = GenerateOptions::new(10)
+///         .with_stop_tokens(vec!["!".to_string()]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GenerateOptions
+{
+        max: u32,
+        stop_tokens: Vec<String>,
+}
+
+impl GenerateOptions
+{
+        /// Creates options with a hard token budget of `max` and no custom
+        /// stop tokens.
+        pub fn new(max: u32) -> Self
+        {
+                Self {
+                        max,
+                        stop_tokens: Vec::new(),
+                }
+        }
+
+        /// Sets the tokens that, besides [`EOS`], also halt generation,
+        /// replacing any stop tokens set before.
+        pub fn with_stop_tokens(mut self, stop_tokens: Vec<String>) -> Self
+        {
+                self.stop_tokens = stop_tokens;
+                self
+        }
+}
+
+/// Why [`Model::generate_with_options()`] stopped producing tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason
+{
+        /// The model predicted [`EOS`].
+        Eos,
+        /// The model predicted one of `GenerateOptions::stop_tokens`.
+        StopToken,
+        /// The `max` token budget was exhausted.
+        Budget,
+}
+
+/// Result of [`Model::generate_with_options()`].
+#[derive(Debug, Clone)]
+pub struct GenerateReport
+{
+        /// Number of tokens actually produced.
+        pub produced: u32,
+        /// Number of tokens left in the budget when generation halted.
+        pub remaining: u32,
+        /// Why generation halted.
+        pub reason: StopReason,
+}
+
+/// A candidate sequence tracked during beam search.
+///
+/// Ordered so that the *lowest* `log_prob` sorts as the greatest element,
+/// which makes [`BinaryHeap::pop()`] evict the weakest beam first.
+#[derive(Debug, Clone, PartialEq)]
+struct Sequence
+{
+        tokens: Vec<String>,
+        log_prob: f32,
+}
+
+impl Eq for Sequence {}
+
+impl PartialOrd for Sequence
+{
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering>
+        {
+                Some(self.cmp(other))
+        }
+}
+
+impl Ord for Sequence
+{
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering
+        {
+                other.log_prob.total_cmp(&self.log_prob)
+        }
+}
+
 fn cut(tokens: Vec<String>, context: usize) -> Vec<String>
 {
         if tokens.len() <= context {
@@ -198,11 +306,16 @@ fn cut(tokens: Vec<String>, context: usize) -> Vec<String>
 /// let config = Config::new(3, true, DEFAULT_SAMPLING);
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "saveload", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config
 {
         context: usize,
         smoothing: bool,
         sampling: f32,
+        beam_width: usize,
+        temperature: f32,
+        floor_probability: f32,
+        weighted: bool,
 }
 
 impl Config
@@ -213,8 +326,61 @@ impl Config
                         context,
                         smoothing,
                         sampling,
+                        beam_width: DEFAULT_BEAM_WIDTH,
+                        temperature: DEFAULT_TEMPERATURE,
+                        floor_probability: DEFAULT_FLOOR_PROBABILITY,
+                        weighted: DEFAULT_WEIGHTED,
                 }
         }
+
+        /// Sets number of candidate sequences kept by [`Model::generate_beam()`].
+        pub fn with_beam_width(mut self, beam_width: usize) -> Self
+        {
+                self.beam_width = beam_width;
+                self
+        }
+
+        /// Sets the softmax temperature used by [`Model::predict()`].
+        ///
+        /// Values close to `0.0` approach greedy/argmax selection, `1.0`
+        /// reproduces the maximum-likelihood distribution, and values above
+        /// `1.0` flatten the distribution toward uniform.
+        pub fn with_temperature(mut self, temperature: f32) -> Self
+        {
+                self.temperature = temperature;
+                self
+        }
+
+        /// Sets the probability assigned to unseen contexts/tokens by
+        /// [`Model::log_prob()`], so that [`Model::perplexity()`] stays finite.
+        pub fn with_floor_probability(mut self, floor_probability: f32) -> Self
+        {
+                self.floor_probability = floor_probability;
+                self
+        }
+
+        /// Switches [`Model::predict()`] to count-weighted selection, picking a
+        /// continuation with probability proportional to its raw observed
+        /// count instead of the temperature-scaled softmax.
+        pub fn with_weighted(mut self, weighted: bool) -> Self
+        {
+                self.weighted = weighted;
+                self
+        }
+
+        /// Reads `context`, `smoothing` and `sampling` from a JSON or TOML
+        /// document (`.toml` extension selects TOML, anything else JSON).
+        ///
+        /// # Note
+        ///
+        /// See [`Model::train_from_file()`] to also pull a corpus out of the
+        /// same document and train a model in one call.
+        #[cfg(feature = "saveload")]
+        pub fn from_file(path: &str) -> Result_<Self>
+        {
+                let document = ConfigFile::read(path)?;
+                Ok(Self::new(document.context, document.smoothing, document.sampling))
+        }
 }
 
 impl Default for Config
@@ -222,9 +388,13 @@ impl Default for Config
         fn default() -> Self
         {
                 Self {
-                        context: 2,
-                        smoothing: true,
-                        sampling: 0.8,
+                        context: DEFAULT_CONTEXT,
+                        smoothing: DEFAULT_SMOOTHING,
+                        sampling: DEFAULT_SAMPLING,
+                        beam_width: DEFAULT_BEAM_WIDTH,
+                        temperature: DEFAULT_TEMPERATURE,
+                        floor_probability: DEFAULT_FLOOR_PROBABILITY,
+                        weighted: DEFAULT_WEIGHTED,
                 }
         }
 }
@@ -345,25 +515,53 @@ impl Model
         {
                 let tokens = cut(tokens, self.config.context);
                 if let Some(counts) = self.get(tokens) {
-                        {
-                                let mut counts = counts.iter().collect::<Vec<_>>();
-                                counts.sort_by(|a, b| b.1.cmp(&a.1));
-                                let samples = max(
-                                        1,
-                                        (counts.len() as f32 * self.config.sampling) as usize,
-                                ); // at least one sample
-                                counts.into_iter()
-                                        .map(|(k, _)| k)
-                                        .take(samples)
-                                        .collect::<Vec<_>>()
+                        let mut counts = counts.iter().collect::<Vec<_>>();
+                        counts.sort_by(|a, b| b.1.cmp(&a.1));
+                        let samples = max(
+                                1,
+                                (counts.len() as f32 * self.config.sampling) as usize,
+                        ); // at least one sample
+                        let candidates = counts.into_iter().take(samples).collect::<Vec<_>>();
+
+                        if self.config.weighted {
+                                // Walk the candidates, picking one with probability
+                                // proportional to its raw observed count.
+                                let total = candidates.iter().map(|(_, count)| **count).sum::<u32>();
+                                let mut remainder = rand::thread_rng().gen_range(0..total);
+                                candidates
+                                        .into_iter()
+                                        .find(|(_, count)| match remainder.checked_sub(**count) {
+                                                Some(r) => {
+                                                        remainder = r;
+                                                        false
+                                                }
+                                                None => true,
+                                        })
+                                        .map(|(token, _)| token.to_string())
+                                        .unwrap()
+                        }
+                        else {
+                                // logits -> temperature-scaled softmax (max-subtracted for stability)
+                                let temperature = self.config.temperature.max(f32::EPSILON);
+                                let logits = candidates
+                                        .iter()
+                                        .map(|(_, count)| (**count as f32).ln() / temperature)
+                                        .collect::<Vec<_>>();
+                                let max_logit = logits.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+                                let weights = logits
+                                        .iter()
+                                        .map(|logit| (logit - max_logit).exp())
+                                        .collect::<Vec<_>>();
+
+                                let distribution = WeightedIndex::new(&weights).unwrap();
+                                candidates[distribution.sample(&mut rand::thread_rng())]
+                                        .0
+                                        .to_string()
                         }
-                        .choose(&mut rand::thread_rng())
-                        .unwrap()
                 }
                 else {
-                        EOS
+                        EOS.to_string()
                 }
-                .to_string()
         }
 
         /// Generates tokens using [`Model::predict()`].
@@ -399,17 +597,247 @@ impl Model
                         }
                 }
         }
+
+        /// Generates tokens using beam search instead of single-sample [`Model::predict()`].
+        ///
+        /// Keeps the `beam_width` (see [`Config::with_beam_width()`]) best-scoring
+        /// sequences alive at each step, scoring every beam by the summed
+        /// log-probability of its tokens, and returns the highest-scoring
+        /// sequence once it emits EOS (or the best incomplete one after `max`
+        /// tokens).
+        ///
+        /// # Usage
+        ///
+        /// ```
+        /// use n_gram::{
+        ///         tokenize,
+        ///         Config,
+        ///         Model,
+        /// };
+        ///
+        /// let model = Model::new(Config::default()); // assuming that your model is trained.
+        /// let tokens = tokenize("The quick brown".to_string());
+        /// let max = 10; // max 10 generated tokens.
+        ///
+        /// let best = model.generate_beam(&tokens, max);
+        /// println!("{best:?}");
+        /// ```
+        pub fn generate_beam(&self, tokens: &[String], max: u32) -> Vec<String>
+        {
+                let mut beams = BinaryHeap::new();
+                beams.push(Sequence {
+                        tokens: tokens.to_vec(),
+                        log_prob: 0.0,
+                });
+                let mut finished: Option<Sequence> = None;
+
+                for _ in 0..max {
+                        if beams.is_empty() {
+                                break;
+                        }
+
+                        let mut candidates = BinaryHeap::new();
+                        for beam in beams.drain() {
+                                if beam.tokens.last().map(|t| t.as_str()) == Some(EOS) {
+                                        if finished.as_ref().is_none_or(|f| beam.log_prob > f.log_prob) {
+                                                finished = Some(beam);
+                                        }
+                                        continue;
+                                }
+
+                                let context = cut(beam.tokens.clone(), self.config.context);
+                                if let Some(counts) = self.get(context) {
+                                        let total = counts.values().sum::<u32>();
+                                        let mut counts = counts.iter().collect::<Vec<_>>();
+                                        counts.sort_by(|a, b| b.1.cmp(a.1));
+
+                                        for (token, count) in counts.into_iter().take(self.config.beam_width) {
+                                                if *count == 0 {
+                                                        continue;
+                                                }
+
+                                                let mut tokens = beam.tokens.clone();
+                                                tokens.push(token.clone());
+                                                candidates.push(Sequence {
+                                                        tokens,
+                                                        log_prob: beam.log_prob + (*count as f32 / total as f32).ln(),
+                                                });
+
+                                                if candidates.len() > self.config.beam_width {
+                                                        candidates.pop();
+                                                }
+                                        }
+                                }
+                                else {
+                                        // Empty backoff distribution - treat the beam as terminated.
+                                        let mut tokens = beam.tokens.clone();
+                                        tokens.push(EOS.to_string());
+                                        let beam = Sequence {
+                                                tokens,
+                                                log_prob: beam.log_prob,
+                                        };
+                                        if finished.as_ref().is_none_or(|f| beam.log_prob > f.log_prob) {
+                                                finished = Some(beam);
+                                        }
+                                }
+                        }
+
+                        beams = candidates;
+                }
+
+                // Beams that emitted EOS on the last step are completed too.
+                let (completed, live): (Vec<_>, Vec<_>) = beams
+                        .into_iter()
+                        .partition(|beam| beam.tokens.last().map(|t| t.as_str()) == Some(EOS));
+                for beam in completed {
+                        if finished.as_ref().is_none_or(|f| beam.log_prob > f.log_prob) {
+                                finished = Some(beam);
+                        }
+                }
+
+                // A completed sequence always wins over a still-live beam, even if
+                // the live beam's partial score is higher.
+                match finished {
+                        Some(sequence) => sequence.tokens,
+                        None => live
+                                .into_iter()
+                                .max_by(|a, b| a.log_prob.total_cmp(&b.log_prob))
+                                .map(|s| s.tokens)
+                                .unwrap_or_default(),
+                }
+        }
+
+        /// Generates tokens like [`Model::generate()`], but bounded by a
+        /// [`GenerateOptions`] budget and an optional set of custom stop
+        /// tokens, reporting why and where generation halted.
+        ///
+        /// # Usage
+        ///
+        /// ```
+        /// use n_gram::{
+        ///         tokenize,
+        ///         Config,
+        ///         GenerateOptions,
+        ///         Model,
+        /// };
+        ///
+        /// let model = Model::new(Config::default()); // assuming that your model is trained.
+        /// let mut tokens = tokenize("The quick brown".to_string());
+        /// let options = GenerateOptions::new(10).with_stop_tokens(vec!["!".to_string()]);
+        ///
+        /// let report = model.generate_with_options(&mut tokens, &options);
+        /// println!("{tokens:?} ({report:?})");
+        /// ```
+        pub fn generate_with_options(
+                &self,
+                tokens: &mut Vec<String>,
+                options: &GenerateOptions,
+        ) -> GenerateReport
+        {
+                let mut produced = 0;
+                let reason = loop {
+                        if produced >= options.max {
+                                break StopReason::Budget;
+                        }
+
+                        let token = self.predict(cut(tokens.to_vec(), self.config.context));
+                        tokens.push(token.clone());
+                        produced += 1;
+
+                        if token == EOS {
+                                break StopReason::Eos;
+                        }
+                        if options.stop_tokens.contains(&token) {
+                                break StopReason::StopToken;
+                        }
+                };
+
+                GenerateReport {
+                        produced,
+                        remaining: options.max - produced,
+                        reason,
+                }
+        }
+}
+
+impl Model
+{
+        /// Returns the summed log-probability the model assigns to `tokens`.
+        ///
+        /// Walks each position, cuts to `context`, resolves the count
+        /// distribution (honoring backoff when `smoothing` is on), and adds
+        /// `ln(count / total)`. Unseen contexts/tokens fall back to
+        /// `floor_probability` so the result stays finite.
+        ///
+        /// # Usage
+        ///
+        /// ```
+        /// use n_gram::{
+        ///         tokenize,
+        ///         Config,
+        ///         Model,
+        /// };
+        ///
+        /// let model = Model::new(Config::default()); // assuming that your model is trained.
+        /// let tokens = tokenize("The quick brown".to_string());
+        ///
+        /// let log_prob = model.log_prob(&tokens);
+        /// println!("{log_prob}");
+        /// ```
+        pub fn log_prob(&self, tokens: &[String]) -> f32
+        {
+                let mut log_prob = 0.0;
+                for i in 0..tokens.len() {
+                        let context = cut(tokens[..i].to_vec(), self.config.context);
+                        let probability = self
+                                .get(context)
+                                .and_then(|counts| {
+                                        let total = counts.values().sum::<u32>();
+                                        counts.get(&tokens[i]).map(|count| *count as f32 / total as f32)
+                                })
+                                .unwrap_or(self.config.floor_probability);
+                        log_prob += probability.ln();
+                }
+                log_prob
+        }
+
+        /// Returns the perplexity of the model over `corpus`.
+        ///
+        /// Computed as `exp(-total_log_prob / total_tokens)` using
+        /// [`Model::log_prob()`] for every sentence in `corpus`.
+        ///
+        /// # Usage
+        ///
+        /// ```
+        /// use n_gram::{
+        ///         tokenize,
+        ///         Config,
+        ///         Model,
+        /// };
+        ///
+        /// let model = Model::new(Config::default()); // assuming that your model is trained.
+        /// let corpus = vec![tokenize("The quick brown".to_string())];
+        ///
+        /// let perplexity = model.perplexity(&corpus);
+        /// println!("{perplexity}");
+        /// ```
+        pub fn perplexity(&self, corpus: &[Vec<String>]) -> f32
+        {
+                let total_log_prob = corpus.iter().map(|tokens| self.log_prob(tokens)).sum::<f32>();
+                let total_tokens = corpus.iter().map(|tokens| tokens.len()).sum::<usize>();
+                (-total_log_prob / total_tokens as f32).exp()
+        }
 }
 
 #[cfg(feature = "saveload")]
 impl Model
 {
         /// Saves model into json file.
-        /// 
+        ///
         /// # Note
         ///
         /// Returns file.write() status code.
-        fn save(&self, path: &str) -> Result_<usize>
+        pub fn save(&self, path: &str) -> Result_<usize>
         {
                 let mut file = File::create(path)?;
                 let model = self
@@ -423,7 +851,7 @@ impl Model
         }
 
         /// Loads model from json file.
-        fn load(&mut self, path: &str) -> Result_<()>
+        pub fn load(&mut self, path: &str) -> Result_<()>
         {
                 let file = File::open(path)?;
                 let model: Vec<(String, HashMap<String, u32>)> = serde_json::from_reader(file)?;
@@ -440,4 +868,100 @@ impl Model
                         .collect::<HashMap<Vec<String>, HashMap<String, u32>>>();
                 Ok(())
         }
+
+        /// Saves model into a compact binary file using `bincode`.
+        ///
+        /// Serializes the n-gram map and [`Config`] directly, with no
+        /// string-joined key hack, so it has no collision risk when a token
+        /// happens to contain [`SPLIT_TOKEN`] and is substantially smaller
+        /// than [`Model::save()`]'s JSON output.
+        pub fn save_bin(&self, path: &str) -> Result_<()>
+        {
+                let file = File::create(path)?;
+                bincode::serialize_into(file, &(&self.model, &self.config))?;
+                Ok(())
+        }
+
+        /// Loads model from a binary file written by [`Model::save_bin()`].
+        pub fn load_bin(&mut self, path: &str) -> Result_<()>
+        {
+                let file = File::open(path)?;
+                let (model, config) = bincode::deserialize_from(file)?;
+                self.model = model;
+                self.config = config;
+                Ok(())
+        }
+}
+
+/// A training document read by [`Config::from_file()`] and
+/// [`Model::train_from_file()`].
+///
+/// Either `corpus` (inline sentences) or `corpus_path` (a path to a
+/// newline-delimited corpus file) should be set; if both are set, `corpus`
+/// wins.
+#[cfg(feature = "saveload")]
+#[derive(serde::Deserialize)]
+struct ConfigFile
+{
+        context: usize,
+        smoothing: bool,
+        sampling: f32,
+        #[serde(default)]
+        corpus: Option<Vec<String>>,
+        #[serde(default)]
+        corpus_path: Option<String>,
+}
+
+#[cfg(feature = "saveload")]
+impl ConfigFile
+{
+        fn read(path: &str) -> Result_<Self>
+        {
+                let text = std::fs::read_to_string(path)?;
+                if path.ends_with(".toml") {
+                        Ok(toml::from_str(&text)?)
+                }
+                else {
+                        Ok(serde_json::from_str(&text)?)
+                }
+        }
+}
+
+#[cfg(feature = "saveload")]
+impl Model
+{
+        /// Trains a model straight from a JSON or TOML config document
+        /// specifying `context`, `smoothing`, `sampling`, and either an
+        /// inline `corpus` (list of sentences) or a `corpus_path` to a
+        /// newline-delimited corpus file.
+        ///
+        /// # Usage
+        ///
+        /// ```no_run
+        /// use n_gram::Model;
+        ///
+        /// let model = Model::train_from_file("config.json").unwrap();
+        /// ```
+        pub fn train_from_file(path: &str) -> Result_<Self>
+        {
+                let document = ConfigFile::read(path)?;
+                let sentences = match (document.corpus, document.corpus_path) {
+                        (Some(sentences), _) => sentences,
+                        (None, Some(corpus_path)) => std::fs::read_to_string(corpus_path)?
+                                .lines()
+                                .map(|line| line.to_string())
+                                .collect(),
+                        (None, None) => Vec::new(),
+                };
+
+                let config = Config::new(document.context, document.smoothing, document.sampling);
+                let mut model = Self::new(config);
+                model.train(
+                        sentences
+                                .into_iter()
+                                .map(|sentence| sos(eos(tokenize(sentence))))
+                                .collect(),
+                );
+                Ok(model)
+        }
 }